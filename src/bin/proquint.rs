@@ -4,7 +4,7 @@ use std::env;
 use std::process::exit;
 use std::str::FromStr;
 use proquint::{AsProquint, Proquint};
-use std::net::Ipv4Addr;
+use std::net::IpAddr;
 
 fn exit_usage() -> ! {
         println!("Usage: proquint u64:<int>");
@@ -28,12 +28,10 @@ fn main() {
     } else if inp.starts_with("u16:") {
         u16::from_str(&inp[4..]).unwrap().as_proquint()
     } else if inp.starts_with("ip:") {
-        let ip = Ipv4Addr::from_str(&inp[3..]).unwrap();
-        let o = ip.octets();
-        Proquint::from_slice(&[
-                             ((o[0] as u16) << 8) | o[1] as u16,
-                             ((o[2] as u16) << 8) | o[3] as u16
-                     ])
+        match IpAddr::from_str(&inp[3..]).unwrap() {
+            IpAddr::V4(ip) => ip.as_proquint(),
+            IpAddr::V6(ip) => ip.as_proquint(),
+        }
     } else if inp.starts_with("proquint:") {
         let p = Proquint::from_str(&inp[9..]).unwrap();
         println!("{:?}", p.to_ints());
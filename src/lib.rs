@@ -31,6 +31,7 @@ use std::fmt;
 use std::str;
 use std::str::FromStr;
 use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
 
 const UINT2CONSONANT: &'static [u8] = b"bdfghjklmnprstvz";
 const UINT2VOWEL: &'static [u8] = b"aiou";
@@ -119,10 +120,36 @@ impl Proquint {
         Ok(())
     }
 
-//    /// Convert Proquint to bytes
-//    pub fn to_bytes(&self) -> Vec<u8> {
-//        self.to_ints().iter()
-//    }
+    /// Build a Proquint from an arbitrary byte slice.
+    ///
+    /// Bytes are grouped big-endian into 16bit labels; a trailing odd byte fills
+    /// the high half of its label and the low half is zero. The original length is not
+    /// retained, so pass it to `to_bytes` to recover the exact input.
+    pub fn from_bytes(bytes: &[u8]) -> Proquint {
+        let mut v = Vec::with_capacity(((bytes.len() + 1) / 2) * 5);
+        for chunk in bytes.chunks(2) {
+            let u = if chunk.len() == 2 {
+                ((chunk[0] as u16) << 8) | chunk[1] as u16
+            } else {
+                (chunk[0] as u16) << 8
+            };
+            quint_to_ascii(u, &mut v);
+        }
+        Proquint { inner: v }
+    }
+
+    /// Convert a Proquint back into bytes, truncating to `expected_len`.
+    ///
+    /// For any `b`, `Proquint::from_bytes(b).to_bytes(b.len())` returns `b`.
+    pub fn to_bytes(&self, expected_len: usize) -> Vec<u8> {
+        let mut v = Vec::with_capacity(self.inner.len() / 5 * 2);
+        for u in self.to_ints() {
+            v.push((u >> 8) as u8);
+            v.push((u & 0xff) as u8);
+        }
+        v.truncate(expected_len);
+        v
+    }
 
     /// Convert a Proquint to binary
     pub fn to_ints(&self) -> Vec<u16> {
@@ -145,6 +172,55 @@ impl Proquint {
         }
         v
     }
+
+    /// Decode the Proquint one label at a time.
+    ///
+    /// Unlike `to_ints`, this never panics: a corrupt buffer yields an
+    /// `Err(ProquintError::InvalidCharAt { .. })` pinpointing the offending
+    /// byte, so a long identifier can be validated incrementally.
+    pub fn labels(&self) -> DecodeIter {
+        DecodeIter { inner: &self.inner, pos: 0 }
+    }
+}
+
+/// Iterator yielded by [`Proquint::labels`], decoding 5 letter labels lazily.
+pub struct DecodeIter<'a> {
+    inner: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for DecodeIter<'a> {
+    type Item = Result<u16, ProquintError>;
+
+    fn next(&mut self) -> Option<Result<u16, ProquintError>> {
+        if self.pos >= self.inner.len() {
+            return None;
+        }
+
+        let label = &self.inner[self.pos..];
+        if label.len() < 5 {
+            self.pos = self.inner.len();
+            return Some(Err(ProquintError::InvalidLabelLength));
+        }
+
+        let mut val = 0u16;
+        for (i, c) in label[..5].iter().enumerate() {
+            if UINT2CONSONANT.contains(c) {
+                val <<= 4;
+                val += UINT2CONSONANT.iter().position(|&x| x == *c).unwrap() as u16;
+            } else if UINT2VOWEL.contains(c) {
+                val <<= 2;
+                val += UINT2VOWEL.iter().position(|&x| x == *c).unwrap() as u16;
+            } else {
+                let index = self.pos + i;
+                self.pos = self.inner.len();
+                return Some(Err(ProquintError::InvalidCharAt { byte: *c, index: index }));
+            }
+        }
+
+        self.pos += 5;
+        Some(Ok(val))
+    }
 }
 
 impl FromIterator<u16> for Proquint {
@@ -163,6 +239,52 @@ pub enum ProquintError {
     InvalidLabelLength,
     InvalidConsonant(u8),
     InvalidVowel(u8),
+    /// The Proquint did not hold the number of labels the target type expects
+    InvalidLabelCount,
+    /// A non proquint character was found at `index` while decoding
+    InvalidCharAt { byte: u8, index: usize },
+}
+
+impl fmt::Display for ProquintError {
+    fn fmt(&self, fm: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProquintError::InvalidLabelLength =>
+                write!(fm, "label is not 5 characters long"),
+            ProquintError::InvalidConsonant(c) =>
+                write!(fm, "invalid consonant '{}'", c as char),
+            ProquintError::InvalidVowel(c) =>
+                write!(fm, "invalid vowel '{}'", c as char),
+            ProquintError::InvalidLabelCount =>
+                write!(fm, "unexpected number of labels"),
+            ProquintError::InvalidCharAt { byte, index } =>
+                write!(fm, "invalid character '{}' at index {}", byte as char, index),
+        }
+    }
+}
+
+impl std::error::Error for ProquintError {}
+
+impl Proquint {
+    /// Parse a Proquint tolerating ASCII whitespace and uppercase letters.
+    ///
+    /// Unlike `from_str`, separators and spacing are ignored and uppercase
+    /// letters are lowercased, so a human-written identifier such as
+    /// `"LUSAB - BABAD"` parses into the same value as `"lusab-babad"`.
+    pub fn from_str_lenient(s: &str) -> Result<Proquint, ProquintError> {
+        let mut buf = Vec::with_capacity(s.len());
+        for b in s.bytes() {
+            if b.is_ascii_whitespace() || b == b'-' {
+                continue;
+            }
+            buf.push(b.to_ascii_lowercase());
+        }
+
+        let mut p = Proquint { inner: Vec::with_capacity(buf.len()) };
+        for label in buf.chunks(5) {
+            try!(p.append_label(label));
+        }
+        Ok(p)
+    }
 }
 
 impl FromStr for Proquint {
@@ -260,6 +382,74 @@ impl AsProquint for Ipv4Addr {
     }
 }
 
+impl AsProquint for Ipv6Addr {
+    fn into_proquint(&self, to: &mut Proquint) {
+        for segment in &self.segments() {
+            to.append(*segment);
+        }
+    }
+}
+
+pub trait FromProquint: Sized {
+    /// Rebuild this type from a Proquint, validating that it carries the
+    /// expected number of 5 letter labels
+    fn from_proquint(p: &Proquint) -> Result<Self, ProquintError>;
+}
+
+impl FromProquint for u16 {
+    fn from_proquint(p: &Proquint) -> Result<u16, ProquintError> {
+        let ints = p.to_ints();
+        if ints.len() != 1 {
+            return Err(ProquintError::InvalidLabelCount);
+        }
+        Ok(ints[0])
+    }
+}
+impl FromProquint for u32 {
+    fn from_proquint(p: &Proquint) -> Result<u32, ProquintError> {
+        let ints = p.to_ints();
+        if ints.len() != 2 {
+            return Err(ProquintError::InvalidLabelCount);
+        }
+        Ok(((ints[0] as u32) << 16) | ints[1] as u32)
+    }
+}
+impl FromProquint for u64 {
+    fn from_proquint(p: &Proquint) -> Result<u64, ProquintError> {
+        let ints = p.to_ints();
+        if ints.len() != 4 {
+            return Err(ProquintError::InvalidLabelCount);
+        }
+        Ok(((ints[0] as u64) << 48) | ((ints[1] as u64) << 32)
+           | ((ints[2] as u64) << 16) | ints[3] as u64)
+    }
+}
+impl FromProquint for Ipv4Addr {
+    fn from_proquint(p: &Proquint) -> Result<Ipv4Addr, ProquintError> {
+        let ints = p.to_ints();
+        if ints.len() != 2 {
+            return Err(ProquintError::InvalidLabelCount);
+        }
+        Ok(Ipv4Addr::new((ints[0] >> 8) as u8, (ints[0] & 0xff) as u8,
+                         (ints[1] >> 8) as u8, (ints[1] & 0xff) as u8))
+    }
+}
+impl FromProquint for Ipv6Addr {
+    fn from_proquint(p: &Proquint) -> Result<Ipv6Addr, ProquintError> {
+        let ints = p.to_ints();
+        if ints.len() != 8 {
+            return Err(ProquintError::InvalidLabelCount);
+        }
+        Ok(Ipv6Addr::new(ints[0], ints[1], ints[2], ints[3],
+                         ints[4], ints[5], ints[6], ints[7]))
+    }
+}
+impl FromProquint for Vec<u16> {
+    fn from_proquint(p: &Proquint) -> Result<Vec<u16>, ProquintError> {
+        Ok(p.to_ints())
+    }
+}
+
 #[test]
 fn sanity() {
     assert_eq!(UINT2CONSONANT.len(), 16);
@@ -386,6 +576,56 @@ fn test_proquint_from_ip() {
     assert_eq!(s, "budov-kuras");
 }
 
+#[test]
+fn test_labels_iter() {
+    let p = 0x12345678u32.as_proquint();
+    let labels: Result<Vec<u16>, _> = p.labels().collect();
+    assert_eq!(labels, Ok(vec![0x1234, 0x5678]));
+
+    // A corrupted buffer reports exactly where the bad byte is
+    let mut p = 1u16.as_proquint();
+    p.inner[2] = b'X';
+    assert_eq!(p.labels().next(),
+               Some(Err(ProquintError::InvalidCharAt { byte: b'X', index: 2 })));
+}
+
+#[test]
+fn test_bytes_roundtrip() {
+    for bytes in [&[][..], &[0x7f], &[0x7f, 0x00], &[1, 2, 3], &[1, 2, 3, 4, 5]].iter() {
+        let p = Proquint::from_bytes(bytes);
+        assert_eq!(p.to_bytes(bytes.len()), bytes.to_vec());
+    }
+
+    // Whole u16 callers keep their existing output
+    assert_eq!(Proquint::from_bytes(&[0, 0, 0, 1]), 1u32.as_proquint());
+}
+
+#[test]
+fn test_from_proquint() {
+    let p = Ipv4Addr::from_str("127.0.0.1").unwrap().as_proquint();
+    assert_eq!(Ipv4Addr::from_proquint(&p), Ok(Ipv4Addr::new(127, 0, 0, 1)));
+
+    let p = 0x1234u16.as_proquint();
+    assert_eq!(u16::from_proquint(&p), Ok(0x1234));
+
+    let p = 0x12345678u32.as_proquint();
+    assert_eq!(u32::from_proquint(&p), Ok(0x12345678));
+
+    let p = 0x123456789abcdef0u64.as_proquint();
+    assert_eq!(u64::from_proquint(&p), Ok(0x123456789abcdef0));
+
+    let p = vec![0u16, 1, 2].as_proquint();
+    assert_eq!(Vec::<u16>::from_proquint(&p), Ok(vec![0u16, 1, 2]));
+
+    let addr = Ipv6Addr::from_str("::1").unwrap();
+    let p = addr.as_proquint();
+    assert_eq!(Ipv6Addr::from_proquint(&p), Ok(addr));
+
+    // Wrong number of labels is rejected rather than silently truncated
+    let p = 1u16.as_proquint();
+    assert_eq!(u32::from_proquint(&p), Err(ProquintError::InvalidLabelCount));
+}
+
 #[test]
 fn test_from_string() {
     assert_eq!(Proquint::from_str("XXX"), Err(ProquintError::InvalidLabelLength));
@@ -397,3 +637,12 @@ fn test_from_string() {
     let p1 = Proquint::from_str("bababbabab").unwrap();
     assert_eq!(p0, p1);
 }
+
+#[test]
+fn test_from_string_lenient() {
+    let p = Ipv4Addr::from_str("127.0.0.1").unwrap().as_proquint();
+    assert_eq!(Proquint::from_str_lenient("LUSAB - BABAD").unwrap(), p);
+    assert_eq!(Proquint::from_str_lenient("  lusab\tbabad\n").unwrap(), p);
+    assert_eq!(Proquint::from_str_lenient("XXXXX"),
+               Err(ProquintError::InvalidConsonant(b'x')));
+}